@@ -1,8 +1,12 @@
+use serde::Deserialize;
 use std::path::Path;
 use std::sync::Arc;
 use zarrs::array::{Array, ArrayBuilder};
-use zarrs::storage::ReadableWritableListableStorage;
+use zarrs::array_subset::ArraySubset;
+use zarrs::node::Node;
+use zarrs::storage::{ReadableStorageTraits, ReadableWritableListableStorage};
 use zarrs::filesystem::FilesystemStore;
+use zarrs_http::HTTPStore;
 
 pub type Store = Arc<FilesystemStore>;
 
@@ -11,12 +15,23 @@ pub fn open_store(root: &Path) -> Result<Store, Box<dyn std::error::Error>> {
     Ok(Arc::new(store))
 }
 
-pub fn open_array(
-    store: &Store,
+/// Open a read-only store backed by a `crops.zarr` published on a static web
+/// server or object store, fetching chunks lazily over HTTP.
+pub fn open_store_http(url: &str) -> Result<Arc<dyn ReadableStorageTraits>, Box<dyn std::error::Error>> {
+    let store = HTTPStore::new(url)?;
+    Ok(Arc::new(store))
+}
+
+/// Open an array from any readable store, not just the filesystem-backed
+/// [`Store`] — this is what lets [`open_store_http`]'s store be used directly.
+pub fn open_array<S>(
+    store: Arc<S>,
     path: &str,
-) -> Result<Array<Arc<dyn ReadableWritableListableStorage>>, Box<dyn std::error::Error>> {
-    let store_trait: Arc<dyn ReadableWritableListableStorage> = store.clone();
-    let array = Array::open(store_trait, path)?;
+) -> Result<Array<Arc<S>>, Box<dyn std::error::Error>>
+where
+    S: ReadableStorageTraits + ?Sized,
+{
+    let array = Array::open(store, path)?;
     Ok(array)
 }
 
@@ -42,15 +57,33 @@ pub fn create_array_u16(
     shape: Vec<u64>,
     chunk_shape: Vec<u64>,
 ) -> Result<Array<Arc<dyn ReadableWritableListableStorage>>, Box<dyn std::error::Error>> {
+    create_array_u16_checked(store, path, shape, chunk_shape, false)
+}
+
+/// Like [`create_array_u16`], but when `crc32c` is set, appends zarrs' crc32c
+/// bytes-to-bytes codec to the codec chain so each stored chunk carries a
+/// trailing checksum that zarrs verifies (and errors out on mismatch) when the
+/// chunk is read back.
+pub fn create_array_u16_checked(
+    store: &Store,
+    path: &str,
+    shape: Vec<u64>,
+    chunk_shape: Vec<u64>,
+    crc32c: bool,
+) -> Result<Array<Arc<dyn ReadableWritableListableStorage>>, Box<dyn std::error::Error>> {
+    use zarrs::array::codec::bytes_to_bytes::crc32c::Crc32cCodec;
     use zarrs::array::data_type;
     let store_trait: Arc<dyn ReadableWritableListableStorage> = store.clone();
-    let array = ArrayBuilder::new(
+    let mut builder = ArrayBuilder::new(
         shape.clone(),
         chunk_shape.clone(),
         data_type::uint16(),
         0u16,
-    )
-    .build(store_trait, path)?;
+    );
+    if crc32c {
+        builder.bytes_to_bytes_codecs(vec![Arc::new(Crc32cCodec::new())]);
+    }
+    let array = builder.build(store_trait, path)?;
     array.store_metadata()?;
     Ok(array)
 }
@@ -63,3 +96,142 @@ pub fn write_chunk_u16(
     array.store_chunk_elements(chunk_indices, data)?;
     Ok(())
 }
+
+/// Like [`create_array_u16`], but shards `inner_chunk_shape`-sized chunks into
+/// `shard_shape`-sized stored objects via zarrs' sharding_indexed codec, so one
+/// stored object holds a grid of inner chunks plus an index instead of one file
+/// per chunk. Reads continue through the existing [`open_array`]/[`Array::open`]
+/// path since the codec is self-describing in metadata.
+pub fn create_array_u16_sharded(
+    store: &Store,
+    path: &str,
+    shape: Vec<u64>,
+    shard_shape: Vec<u64>,
+    inner_chunk_shape: Vec<u64>,
+) -> Result<Array<Arc<dyn ReadableWritableListableStorage>>, Box<dyn std::error::Error>> {
+    use std::num::NonZeroU64;
+    use zarrs::array::codec::array_to_bytes::sharding::ShardingCodecBuilder;
+    use zarrs::array::data_type;
+    let store_trait: Arc<dyn ReadableWritableListableStorage> = store.clone();
+    let inner_chunk_shape: Vec<NonZeroU64> = inner_chunk_shape
+        .into_iter()
+        .map(NonZeroU64::try_from)
+        .collect::<Result<_, _>>()?;
+    let sharding_codec = ShardingCodecBuilder::new(inner_chunk_shape.into()).build();
+    let array = ArrayBuilder::new(shape, shard_shape, data_type::uint16(), 0u16)
+        .array_to_bytes_codec(Arc::new(sharding_codec))
+        .build(store_trait, path)?;
+    array.store_metadata()?;
+    Ok(array)
+}
+
+/// Like [`create_array_u16`], but builds the array with a [`RectangularChunkGrid`]
+/// instead of a uniform chunk shape, so axes like `t`/`c` can be chunked into
+/// irregular segments while spatial axes stay regular. `chunk_shapes` holds one
+/// list of (non-zero) chunk sizes per axis, in the same order as `shape`; each
+/// list becomes that axis' [`RectangularChunkGridDimension::Varying`] config.
+pub fn create_array_u16_rectangular(
+    store: &Store,
+    path: &str,
+    shape: Vec<u64>,
+    chunk_shapes: Vec<Vec<u64>>,
+) -> Result<Array<Arc<dyn ReadableWritableListableStorage>>, Box<dyn std::error::Error>> {
+    use std::num::NonZeroU64;
+    use zarrs::array::chunk_grid::{ChunkGrid, RectangularChunkGrid, RectangularChunkGridDimension};
+    use zarrs::array::data_type;
+    let store_trait: Arc<dyn ReadableWritableListableStorage> = store.clone();
+    let mut dimensions = Vec::with_capacity(chunk_shapes.len());
+    for sizes in chunk_shapes {
+        let sizes: Vec<NonZeroU64> = sizes
+            .into_iter()
+            .map(NonZeroU64::try_from)
+            .collect::<Result<_, _>>()?;
+        dimensions.push(RectangularChunkGridDimension::Varying(sizes.into()));
+    }
+    let chunk_grid: ChunkGrid = RectangularChunkGrid::new(dimensions).into();
+    let array = ArrayBuilder::new(shape, chunk_grid, data_type::uint16(), 0u16)
+        .build(store_trait, path)?;
+    array.store_metadata()?;
+    Ok(array)
+}
+
+/// Read an arbitrary `(t, c, z, y, x)` bounding-box region in one call, regardless
+/// of how it straddles the chunk grid.
+pub fn read_subset_u16(
+    array: &Array<impl zarrs::storage::ReadableStorageTraits>,
+    subset: &ArraySubset,
+) -> Result<Vec<u16>, Box<dyn std::error::Error>> {
+    let data = array.retrieve_array_subset_elements::<u16>(subset)?;
+    Ok(data)
+}
+
+/// Write a region that may span multiple chunks in one call. Touched chunks are
+/// written individually, not as a single atomic transaction — if a chunk write
+/// partway through fails, earlier chunks in the region are left written and the
+/// error is returned so the caller can retry or clean up.
+pub fn write_subset_u16(
+    array: &Array<impl zarrs::storage::ReadableWritableStorageTraits>,
+    subset: &ArraySubset,
+    data: &[u16],
+) -> Result<(), Box<dyn std::error::Error>> {
+    array.store_array_subset_elements(subset, data)?;
+    Ok(())
+}
+
+/// Mirrors the manual Zarr v2 writer's `BboxAttrs` shape, so the `.zattrs`
+/// written by `write_crops_group` can be deserialized back here.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct BboxAttrs {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Deserialize)]
+struct CropEntry {
+    path: String,
+    bbox: BboxAttrs,
+}
+
+#[derive(Deserialize)]
+struct GroupZattrs {
+    #[serde(default)]
+    crops: Vec<CropEntry>,
+}
+
+/// Like [`read_subset_u16`], but returns a shaped `ndarray::ArrayD` instead of
+/// a flat `Vec`, so callers can directly index `(t, c, z, y, x)` axes and
+/// reduce over `y`/`x` — e.g. for the per-`(t, c, z)` background medians
+/// described in `write_background_zattrs` — without manual stride arithmetic.
+pub fn read_subset_ndarray_u16(
+    array: &Array<impl zarrs::storage::ReadableStorageTraits>,
+    subset: &ArraySubset,
+) -> Result<ndarray::ArrayD<u16>, Box<dyn std::error::Error>> {
+    let data = array.retrieve_array_subset_ndarray::<u16>(subset)?;
+    Ok(data)
+}
+
+/// Like [`read_subset_ndarray_u16`], but for `f64` arrays (e.g. the background
+/// array written by the manual Zarr v2 writer's `write_array_f64`).
+pub fn read_subset_ndarray_f64(
+    array: &Array<impl zarrs::storage::ReadableStorageTraits>,
+    subset: &ArraySubset,
+) -> Result<ndarray::ArrayD<f64>, Box<dyn std::error::Error>> {
+    let data = array.retrieve_array_subset_ndarray::<f64>(subset)?;
+    Ok(data)
+}
+
+/// List every crop array recorded in the root group's `.zattrs` (written by
+/// `write_crops_group`) along with its bbox, in one metadata read instead of
+/// a directory scan.
+pub fn read_crops_group(
+    store: &Store,
+    path: &str,
+) -> Result<Vec<(String, BboxAttrs)>, Box<dyn std::error::Error>> {
+    let store_trait: Arc<dyn ReadableWritableListableStorage> = store.clone();
+    let node = Node::new(store_trait, path)?;
+    let attrs = node.metadata().attributes();
+    let group: GroupZattrs = serde_json::from_value(serde_json::Value::Object(attrs.clone()))?;
+    Ok(group.crops.into_iter().map(|c| (c.path, c.bbox)).collect())
+}
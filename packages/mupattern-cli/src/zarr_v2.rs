@@ -1,17 +1,111 @@
 // Manual Zarr v2 write for compatibility with existing crops.zarr (Node zarrita).
 // Uses .zarray metadata and dot-separated chunk keys.
 
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::Serialize;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
+/// Numcodecs-compatible compressor for a chunk, serialized into `.zarray`'s
+/// `compressor` field exactly as Node zarrita expects it.
+#[derive(Clone, Copy, Debug)]
+pub enum Compressor {
+    Gzip { level: u32 },
+    Blosc {
+        cname: &'static str,
+        clevel: u32,
+        shuffle: u32,
+    },
+}
+
+impl Compressor {
+    fn to_json(self) -> serde_json::Value {
+        match self {
+            Compressor::Gzip { level } => serde_json::json!({ "id": "gzip", "level": level }),
+            Compressor::Blosc {
+                cname,
+                clevel,
+                shuffle,
+            } => serde_json::json!({
+                "id": "blosc",
+                "cname": cname,
+                "clevel": clevel,
+                "shuffle": shuffle,
+                "blocksize": 0,
+            }),
+        }
+    }
+
+    /// Compress little-endian, C-order element bytes with the matching codec.
+    /// `typesize` is the element size in bytes (e.g. 2 for `<u2`, 8 for `<f8`)
+    /// so blosc's byte/bit shuffle operates on actual elements, not raw `u8`s.
+    fn compress(self, bytes: &[u8], typesize: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            Compressor::Gzip { level } => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            Compressor::Blosc {
+                cname,
+                clevel,
+                shuffle,
+            } => {
+                let ctx = blosc::Context::new()
+                    .compressor(blosc_codec(cname))
+                    .map_err(|_| format!("blosc compressor {cname} unavailable"))?
+                    .clevel(blosc_clevel(clevel))
+                    .shuffle(blosc_shuffle(shuffle))
+                    .typesize(Some(typesize));
+                Ok(ctx.compress(bytes).as_ref().to_vec())
+            }
+        }
+    }
+}
+
+fn blosc_codec(cname: &str) -> blosc::Compressor {
+    match cname {
+        "lz4" => blosc::Compressor::LZ4,
+        "lz4hc" => blosc::Compressor::LZ4HC,
+        "snappy" => blosc::Compressor::Snappy,
+        "zlib" => blosc::Compressor::Zlib,
+        "zstd" => blosc::Compressor::Zstd,
+        _ => blosc::Compressor::BloscLZ,
+    }
+}
+
+fn blosc_clevel(clevel: u32) -> blosc::Clevel {
+    match clevel {
+        0 => blosc::Clevel::L0,
+        1 => blosc::Clevel::L1,
+        2 => blosc::Clevel::L2,
+        3 => blosc::Clevel::L3,
+        4 => blosc::Clevel::L4,
+        5 => blosc::Clevel::L5,
+        6 => blosc::Clevel::L6,
+        7 => blosc::Clevel::L7,
+        8 => blosc::Clevel::L8,
+        _ => blosc::Clevel::L9,
+    }
+}
+
+fn blosc_shuffle(shuffle: u32) -> blosc::ShuffleMode {
+    match shuffle {
+        0 => blosc::ShuffleMode::None,
+        2 => blosc::ShuffleMode::Bit,
+        _ => blosc::ShuffleMode::Byte,
+    }
+}
+
 #[derive(Serialize)]
 struct ZarrayMeta {
     zarr_format: u32,
     shape: Vec<u64>,
     chunks: Vec<u64>,
     dtype: String,
-    compressor: Option<()>,
+    compressor: Option<serde_json::Value>,
     fill_value: Option<serde_json::Value>,
     order: String,
 }
@@ -22,12 +116,12 @@ struct CropZattrs {
     bbox: BboxAttrs,
 }
 
-#[derive(Serialize)]
-struct BboxAttrs {
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
+#[derive(Serialize, Clone, Copy)]
+pub struct BboxAttrs {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
 }
 
 pub fn write_array_u16(
@@ -35,6 +129,7 @@ pub fn write_array_u16(
     array_path: &str,
     shape: Vec<u64>,
     chunks: Vec<u64>,
+    compressor: Option<Compressor>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let dir = root.join(array_path);
     fs::create_dir_all(&dir)?;
@@ -43,7 +138,7 @@ pub fn write_array_u16(
         shape,
         chunks: chunks.clone(),
         dtype: "<u2".to_string(),
-        compressor: None,
+        compressor: compressor.map(Compressor::to_json),
         fill_value: None,
         order: "C".to_string(),
     };
@@ -52,6 +147,45 @@ pub fn write_array_u16(
     Ok(())
 }
 
+/// A crop array's path relative to the group root, paired with its bbox, as
+/// recorded in the group's `.zattrs` so a client can enumerate crops from one
+/// metadata read instead of scanning the filesystem.
+#[derive(Serialize)]
+struct CropEntry {
+    path: String,
+    bbox: BboxAttrs,
+}
+
+#[derive(Serialize)]
+struct GroupZattrs {
+    crops: Vec<CropEntry>,
+}
+
+/// Write the root `.zgroup` and a `.zattrs` listing every crop array path plus
+/// its bbox, tying the per-crop arrays written by [`write_array_u16`] and
+/// [`write_crop_zattrs`] into one discoverable hierarchy. Call once at the end
+/// of a run, after all crops have been written.
+pub fn write_crops_group(
+    root: &Path,
+    crops: &[(String, BboxAttrs)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(root)?;
+    let zgroup = serde_json::json!({ "zarr_format": 2 });
+    fs::write(root.join(".zgroup"), serde_json::to_string(&zgroup)?)?;
+
+    let attrs = GroupZattrs {
+        crops: crops
+            .iter()
+            .map(|(path, bbox)| CropEntry {
+                path: path.clone(),
+                bbox: *bbox,
+            })
+            .collect(),
+    };
+    fs::write(root.join(".zattrs"), serde_json::to_string(&attrs)?)?;
+    Ok(())
+}
+
 pub fn write_crop_zattrs(
     root: &Path,
     array_path: &str,
@@ -91,6 +225,7 @@ pub fn write_array_f64(
     array_path: &str,
     shape: Vec<u64>,
     chunks: Vec<u64>,
+    compressor: Option<Compressor>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let dir = root.join(array_path);
     fs::create_dir_all(&dir)?;
@@ -99,7 +234,7 @@ pub fn write_array_f64(
         shape,
         chunks: chunks.clone(),
         dtype: "<f8".to_string(),
-        compressor: None,
+        compressor: compressor.map(Compressor::to_json),
         fill_value: None,
         order: "C".to_string(),
     };
@@ -113,12 +248,17 @@ pub fn write_chunk_u16(
     array_path: &str,
     chunk_key: &str,
     data: &[u16],
+    compressor: Option<Compressor>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let path = root.join(array_path).join(chunk_key);
     let bytes: Vec<u8> = data
         .iter()
         .flat_map(|v| v.to_le_bytes())
         .collect();
+    let bytes = match compressor {
+        Some(c) => c.compress(&bytes, std::mem::size_of::<u16>())?,
+        None => bytes,
+    };
     fs::write(path, bytes)?;
     Ok(())
 }
@@ -128,8 +268,84 @@ pub fn write_chunk_f64(
     array_path: &str,
     chunk_key: &str,
     value: f64,
+    compressor: Option<Compressor>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let path = root.join(array_path).join(chunk_key);
-    fs::write(path, value.to_le_bytes())?;
+    let bytes = value.to_le_bytes().to_vec();
+    let bytes = match compressor {
+        Some(c) => c.compress(&bytes, std::mem::size_of::<f64>())?,
+        None => bytes,
+    };
+    fs::write(path, bytes)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn gzip_to_json_matches_numcodecs() {
+        let json = Compressor::Gzip { level: 5 }.to_json();
+        assert_eq!(json, serde_json::json!({ "id": "gzip", "level": 5 }));
+    }
+
+    #[test]
+    fn blosc_to_json_matches_numcodecs() {
+        let json = Compressor::Blosc {
+            cname: "zstd",
+            clevel: 5,
+            shuffle: 1,
+        }
+        .to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "blosc",
+                "cname": "zstd",
+                "clevel": 5,
+                "shuffle": 1,
+                "blocksize": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn gzip_round_trips_u16_chunk_bytes() {
+        let data: Vec<u16> = (0..1024).collect();
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        let compressed = Compressor::Gzip { level: 5 }
+            .compress(&bytes, std::mem::size_of::<u16>())
+            .expect("gzip compression should succeed");
+
+        let mut decoder = GzDecoder::new(compressed.as_slice());
+        let mut decompressed = Vec::new();
+        decoder
+            .read_to_end(&mut decompressed)
+            .expect("gzip decompression should succeed");
+
+        assert_eq!(decompressed, bytes);
+    }
+
+    #[test]
+    fn blosc_round_trips_u16_chunk_bytes_with_shuffle() {
+        let data: Vec<u16> = (0..1024).collect();
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        let compressed = Compressor::Blosc {
+            cname: "zstd",
+            clevel: 5,
+            shuffle: 1,
+        }
+        .compress(&bytes, std::mem::size_of::<u16>())
+        .expect("blosc compression should succeed");
+
+        let decompressed: Vec<u8> = unsafe { blosc::decompress_bytes(&compressed) }
+            .expect("blosc decompression should succeed");
+
+        assert_eq!(decompressed, bytes);
+    }
+}